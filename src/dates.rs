@@ -0,0 +1,159 @@
+use chrono::prelude::*;
+use chrono::Duration;
+
+/// Parse a small set of natural-language date/time shorthands relative to `now`.
+///
+/// Recognized forms:
+/// - `today` / `tomorrow`
+/// - `mon`, `tue`, `wed`, `thu`, `fri`, `sat`, `sun` (next occurrence of that weekday)
+/// - `+3d`, `+2w` (offset from now, in days or weeks)
+/// - `HH:MM` (today at that time, rolling to tomorrow if already past)
+///
+/// Anything else returns `None`. When only a date is recognized the time of
+/// day defaults to midnight.
+pub fn parse_fuzzy_date(input: &str, now: DateTime<Local>) -> Option<DateTime<Local>> {
+    let input = input.trim().to_lowercase();
+    if input.is_empty() {
+        return None;
+    }
+
+    if input == "today" {
+        return Some(midnight(now.date()));
+    }
+    if input == "tomorrow" {
+        return Some(midnight(now.date() + Duration::days(1)));
+    }
+
+    if let Some(weekday) = parse_weekday(&input) {
+        let mut date = now.date() + Duration::days(1);
+        for _ in 0..7 {
+            if date.weekday() == weekday {
+                return Some(midnight(date));
+            }
+            date = date + Duration::days(1);
+        }
+        return None;
+    }
+
+    if let Some(offset) = input.strip_prefix('+') {
+        if let Some(amount) = offset.strip_suffix('d') {
+            let days: i64 = amount.parse().ok()?;
+            return Some(now + Duration::days(days));
+        }
+        if let Some(amount) = offset.strip_suffix('w') {
+            let weeks: i64 = amount.parse().ok()?;
+            return Some(now + Duration::weeks(weeks));
+        }
+        return None;
+    }
+
+    if let Some((hour, minute)) = parse_hh_mm(&input) {
+        let mut candidate = now
+            .date()
+            .and_hms_opt(hour, minute, 0)
+            .map(|naive| Local.from_local_datetime(&naive).single())
+            .flatten()?;
+        if candidate <= now {
+            candidate = candidate + Duration::days(1);
+        }
+        return Some(candidate);
+    }
+
+    None
+}
+
+fn midnight(date: Date<Local>) -> DateTime<Local> {
+    date.and_hms(0, 0, 0)
+}
+
+fn parse_weekday(input: &str) -> Option<Weekday> {
+    match input {
+        "mon" => Some(Weekday::Mon),
+        "tue" => Some(Weekday::Tue),
+        "wed" => Some(Weekday::Wed),
+        "thu" => Some(Weekday::Thu),
+        "fri" => Some(Weekday::Fri),
+        "sat" => Some(Weekday::Sat),
+        "sun" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+fn parse_hh_mm(input: &str) -> Option<(u32, u32)> {
+    let (hour_str, minute_str) = input.split_once(':')?;
+    let hour: u32 = hour_str.parse().ok()?;
+    let minute: u32 = minute_str.parse().ok()?;
+    if hour > 23 || minute > 59 {
+        return None;
+    }
+    Some((hour, minute))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(y: i32, m: u32, d: u32, h: u32, mi: u32) -> DateTime<Local> {
+        Local.ymd(y, m, d).and_hms(h, mi, 0)
+    }
+
+    #[test]
+    fn weekday_rolls_over_to_next_week_when_today_matches() {
+        // 2024-01-01 is a Monday; "mon" should never mean "today"
+        let now = at(2024, 1, 1, 9, 0);
+        assert_eq!(parse_fuzzy_date("mon", now), Some(at(2024, 1, 8, 0, 0)));
+    }
+
+    #[test]
+    fn weekday_lands_later_this_week() {
+        let now = at(2024, 1, 1, 9, 0); // Monday
+        assert_eq!(parse_fuzzy_date("fri", now), Some(at(2024, 1, 5, 0, 0)));
+    }
+
+    #[test]
+    fn hh_mm_rolls_to_tomorrow_once_the_time_has_passed() {
+        let now = at(2024, 1, 1, 14, 30);
+        assert_eq!(parse_fuzzy_date("09:00", now), Some(at(2024, 1, 2, 9, 0)));
+    }
+
+    #[test]
+    fn hh_mm_stays_today_while_still_ahead() {
+        let now = at(2024, 1, 1, 9, 0);
+        assert_eq!(parse_fuzzy_date("14:30", now), Some(at(2024, 1, 1, 14, 30)));
+    }
+
+    #[test]
+    fn day_and_week_offsets() {
+        let now = at(2024, 1, 1, 9, 0);
+        assert_eq!(parse_fuzzy_date("+3d", now), Some(now + Duration::days(3)));
+        assert_eq!(parse_fuzzy_date("+2w", now), Some(now + Duration::weeks(2)));
+    }
+
+    #[test]
+    fn invalid_offsets_are_none() {
+        let now = at(2024, 1, 1, 9, 0);
+        assert_eq!(parse_fuzzy_date("+3x", now), None);
+        assert_eq!(parse_fuzzy_date("+d", now), None);
+    }
+
+    #[test]
+    fn invalid_hh_mm_is_none() {
+        let now = at(2024, 1, 1, 9, 0);
+        assert_eq!(parse_fuzzy_date("25:00", now), None);
+        assert_eq!(parse_fuzzy_date("12:60", now), None);
+    }
+
+    #[test]
+    fn input_is_case_insensitive() {
+        let now = at(2024, 1, 1, 9, 0);
+        assert_eq!(parse_fuzzy_date("TODAY", now), parse_fuzzy_date("today", now));
+        assert_eq!(parse_fuzzy_date("Fri", now), parse_fuzzy_date("fri", now));
+    }
+
+    #[test]
+    fn empty_and_unrecognized_input_is_none() {
+        let now = at(2024, 1, 1, 9, 0);
+        assert_eq!(parse_fuzzy_date("", now), None);
+        assert_eq!(parse_fuzzy_date("whenever", now), None);
+    }
+}
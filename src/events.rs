@@ -1,26 +1,21 @@
-use std::time::Instant;
-use std::sync::mpsc;
-use std::thread;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
 use std::time::Duration;
 
-use crossterm::event::{self, KeyCode, Event as CEvent};
+use crossterm::event::{Event as CEvent, EventStream, KeyCode};
+use futures::{Stream, StreamExt};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::{IntervalStream, UnboundedReceiverStream};
 
-//use termion::event::Key;
-//use termion::input::TermRead;
-
-pub enum Event<I> {
-    Input(I),
+pub enum Event {
+    Input(KeyCode),
     Tick,
+    FilesChanged,
 }
 
-/// A small event handler that wrap termion input and tick events. Each event
-/// type is handled in its own thread and returned to a common `Receiver`
-pub struct Events {
-    rx: mpsc::Receiver<Event<KeyCode>>,
-    _input_handle: thread::JoinHandle<()>,
-}
-
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct Config {
     pub tick_rate: Duration,
 }
@@ -33,41 +28,67 @@ impl Default for Config {
     }
 }
 
+/// Merges terminal key events, periodic ticks and filesystem-change
+/// notifications for `watch_paths` into a single async stream.
+pub struct Events {
+    stream: Pin<Box<dyn Stream<Item = Event>>>,
+    // kept alive for as long as Events is, otherwise the watcher stops
+    _watcher: RecommendedWatcher,
+}
+
 impl Events {
-    pub fn new() -> Events {
-        Events::with_config(Config::default())
+    pub fn new(watch_paths: &[&str]) -> Events {
+        Events::with_config(Config::default(), watch_paths)
     }
 
-    pub fn with_config(config: Config) -> Events {
-        let (tx, rx) = mpsc::channel();
-        let _input_handle = {
-            let tx = tx.clone();
-            thread::spawn(move || {
-                //let stdin = io::stdin();
-                let mut last_tick = Instant::now();
-                loop {
-                    let timeout = config.tick_rate
-                        .checked_sub(last_tick.elapsed())
-                        .unwrap_or_else(|| Duration::from_secs(0));
-                    if event::poll(timeout).unwrap() {
-                        if let CEvent::Key(key) = event::read().unwrap() {
-                            tx.send(Event::Input(key.code)).unwrap();
-                        }
-                    }
-                    if last_tick.elapsed() >= config.tick_rate {
-                        tx.send(Event::Tick).unwrap();
-                        last_tick = Instant::now();
-                    }
+    pub fn with_config(config: Config, watch_paths: &[&str]) -> Events {
+        let keys = EventStream::new().filter_map(|event| async move {
+            match event {
+                Ok(CEvent::Key(key)) => Some(Event::Input(key.code)),
+                _ => None,
+            }
+        });
+
+        let ticks = IntervalStream::new(tokio::time::interval(config.tick_rate)).map(|_| Event::Tick);
+
+        // the files we actually care about, so an unrelated write elsewhere in
+        // the watched directory (storage_config.json, sync_config.json, the
+        // .git dir chunk0-4's sync uses) doesn't trigger a reload
+        let watched_files: HashSet<PathBuf> = watch_paths.iter().map(PathBuf::from).collect();
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+            if let Ok(event) = result {
+                if event.paths.iter().any(|path| watched_files.contains(path)) {
+                    let _ = tx.send(());
                 }
             }
-        )};
+        })
+        .expect("failed to start file watcher");
+        // watch the containing directory rather than the file itself: `today.json`
+        // doesn't exist until the first `save_today`, and inotify errors out (with
+        // no retry) if asked to watch a path that isn't there yet
+        let mut watched_dirs = HashSet::new();
+        for path in watch_paths {
+            let dir = Path::new(path)
+                .parent()
+                .filter(|parent| !parent.as_os_str().is_empty())
+                .map(PathBuf::from)
+                .unwrap_or_else(|| PathBuf::from("."));
+            if watched_dirs.insert(dir.clone()) {
+                let _ = watcher.watch(&dir, RecursiveMode::NonRecursive);
+            }
+        }
+        let file_changes = UnboundedReceiverStream::new(rx).map(|_| Event::FilesChanged);
+
+        let merged = futures::stream::select(futures::stream::select(keys, ticks), file_changes);
         Events {
-            rx,
-            _input_handle,
+            stream: Box::pin(merged),
+            _watcher: watcher,
         }
     }
 
-    pub fn next(&self) -> Result<Event<KeyCode>, mpsc::RecvError> {
-        self.rx.recv()
+    pub async fn next(&mut self) -> Option<Event> {
+        self.stream.next().await
     }
 }
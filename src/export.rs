@@ -0,0 +1,149 @@
+use crate::{Status, Task, Today, WeekdayTasks};
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use std::io;
+
+// renders the recurring weekday tasks (overlaid with today's saved tasks, if
+// today falls in the requested week) into week.md and week.html
+pub fn export_week(
+    weekday_tasks: &WeekdayTasks,
+    today_path: &str,
+    week_anchor: Option<&str>,
+    output_dir: &str,
+) -> io::Result<()> {
+    let anchor = match week_anchor {
+        Some(text) => NaiveDate::parse_from_str(text, "%b_%d_%Y")
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?,
+        None => chrono::Local::now().date().naive_local(),
+    };
+    let monday = anchor - Duration::days(anchor.weekday().number_from_monday() as i64 - 1);
+
+    // the app only keeps the most recently saved day on disk, so we can only
+    // fold completed tasks in for the single day that file belongs to
+    let today = crate::format::load::<Today>(today_path).ok();
+
+    let mut days = Vec::new();
+    for offset in 0..7 {
+        let date = monday + Duration::days(offset);
+        let weekday = date.weekday();
+        let day_info = weekday_tasks
+            .tasks
+            .get(&weekday)
+            .map(|day| day.day_info.clone())
+            .unwrap_or_default();
+        let mut tasks: Vec<Task> = weekday_tasks
+            .tasks
+            .get(&weekday)
+            .map(|day| {
+                day.tasks
+                    .iter()
+                    .map(|info| Task {
+                        status: Status::Todo,
+                        info: info.clone(),
+                        priority: Default::default(),
+                        when: None,
+                        deadline: None,
+                        time_entries: Vec::new(),
+                        tags: Vec::new(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        if let Some(today) = &today {
+            if let Some(today_date) = today.date {
+                if today_date.date().naive_local() == date {
+                    tasks = today.tasks.clone();
+                }
+            }
+        }
+        days.push(WeekDay { date, weekday, day_info, tasks });
+    }
+
+    std::fs::write(format!("{}/week.md", output_dir), render_markdown(&days))?;
+    std::fs::write(format!("{}/week.html", output_dir), render_html(&days))?;
+    Ok(())
+}
+
+struct WeekDay {
+    date: NaiveDate,
+    weekday: Weekday,
+    day_info: String,
+    tasks: Vec<Task>,
+}
+
+// escapes `\` and `|` so a task's free-text `info`/`day_info` can't break out
+// of a Markdown table cell, and strips newlines so it can't break out of the
+// row either
+fn escape_markdown_cell(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace('\r', "")
+        .replace('\n', " ")
+}
+
+// escapes the characters Markdown tasks can't otherwise guard against being
+// interpreted as HTML when week.html is opened in a browser
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn render_markdown(days: &[WeekDay]) -> String {
+    let mut out = String::new();
+    for day in days {
+        out.push_str(&format!("| {} ({})", day.weekday, day.date.format("%m-%d")));
+    }
+    out.push_str("|\n");
+    for _ in days {
+        out.push_str("| --- ");
+    }
+    out.push_str("|\n");
+
+    let max_rows = days.iter().map(|day| day.tasks.len()).max().unwrap_or(0);
+    for row in 0..max_rows {
+        for day in days {
+            let cell = day
+                .tasks
+                .get(row)
+                .map(task_checkbox_line)
+                .unwrap_or_default();
+            out.push_str(&format!("| {} ", cell));
+        }
+        out.push_str("|\n");
+    }
+    out
+}
+
+fn task_checkbox_line(task: &Task) -> String {
+    let marker = match task.status {
+        Status::Todo => "[ ]",
+        Status::Done => "[x]",
+    };
+    format!("{} {}", marker, escape_markdown_cell(&task.info))
+}
+
+fn render_html(days: &[WeekDay]) -> String {
+    let mut out = String::from("<html><body><table border=\"1\">\n<tr>");
+    for day in days {
+        out.push_str(&format!(
+            "<th>{} {}<br/><small>{}</small></th>",
+            day.weekday,
+            day.date.format("%m-%d"),
+            escape_html(&day.day_info)
+        ));
+    }
+    out.push_str("</tr>\n<tr>");
+    for day in days {
+        out.push_str("<td><ul>");
+        for task in &day.tasks {
+            let box_token = match task.status {
+                Status::Todo => "☐",
+                Status::Done => "☑",
+            };
+            out.push_str(&format!("<li>{} {}</li>", box_token, escape_html(&task.info)));
+        }
+        out.push_str("</ul></td>");
+    }
+    out.push_str("</tr>\n</table></body></html>");
+    out
+}
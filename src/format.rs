@@ -0,0 +1,155 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::Path;
+
+// which on-disk encoding a path should be read/written with, picked by file
+// extension; `.mp` files round-trip as MessagePack, everything else stays
+// plain JSON
+enum FileFormat {
+    Json,
+    MessagePack,
+}
+
+impl FileFormat {
+    fn for_path(path: &str) -> FileFormat {
+        match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+            Some("mp") => FileFormat::MessagePack,
+            _ => FileFormat::Json,
+        }
+    }
+
+    // encodes the typed value directly, rather than through a generic
+    // `serde_json::Value` intermediate, so MessagePack keeps the compactness
+    // it's chosen for instead of paying to build a throwaway JSON tree first
+    fn serialize<T: Serialize>(&self, value: &T) -> io::Result<Vec<u8>> {
+        match self {
+            FileFormat::Json => serde_json::to_vec(value).map_err(to_io_error),
+            FileFormat::MessagePack => rmp_serde::to_vec(value).map_err(to_io_error),
+        }
+    }
+
+    fn deserialize<T: DeserializeOwned>(&self, bytes: &[u8]) -> io::Result<T> {
+        match self {
+            FileFormat::Json => serde_json::from_slice(bytes).map_err(to_io_error),
+            FileFormat::MessagePack => rmp_serde::from_slice(bytes).map_err(to_io_error),
+        }
+    }
+}
+
+fn to_io_error<E: std::fmt::Display>(err: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err.to_string())
+}
+
+pub fn save<T: Serialize>(path: &str, value: &T) -> io::Result<()> {
+    let bytes = FileFormat::for_path(path).serialize(value)?;
+    std::fs::write(path, bytes)
+}
+
+pub fn load<T: DeserializeOwned>(path: &str) -> io::Result<T> {
+    let bytes = std::fs::read(path)?;
+    FileFormat::for_path(path).deserialize(&bytes)
+}
+
+// picks which extension `today.json`/`daily_occuring.json` are stored under,
+// letting a user opt into the MessagePack backend by editing the settings file
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StorageConfig {
+    pub extension: String,
+}
+
+impl StorageConfig {
+    fn default_json() -> Self {
+        Self {
+            extension: "json".to_string(),
+        }
+    }
+}
+
+// reads the storage settings, seeding the file with the JSON default on first run
+pub fn load_or_init_storage_config(config_path: &str) -> StorageConfig {
+    match std::fs::read_to_string(config_path) {
+        Ok(file_string) => {
+            serde_json::from_str(&file_string).unwrap_or_else(|_| StorageConfig::default_json())
+        }
+        Err(_file_error) => {
+            let default_config = StorageConfig::default_json();
+            let serialized = serde_json::to_string(&default_config).unwrap();
+            let _save_result = std::fs::write(config_path, serialized);
+            default_config
+        }
+    }
+}
+
+// swaps `path`'s extension for the configured storage extension (e.g. "mp")
+pub fn with_storage_extension(path: &str, storage_config: &StorageConfig) -> String {
+    Path::new(path)
+        .with_extension(&storage_config.extension)
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Sample {
+        name: String,
+        count: u32,
+        tags: Vec<String>,
+    }
+
+    fn sample() -> Sample {
+        Sample {
+            name: "groceries".to_string(),
+            count: 3,
+            tags: vec!["home".to_string(), "errand".to_string()],
+        }
+    }
+
+    #[test]
+    fn for_path_picks_messagepack_only_for_mp_extension() {
+        assert!(matches!(FileFormat::for_path("today.mp"), FileFormat::MessagePack));
+        assert!(matches!(FileFormat::for_path("today.json"), FileFormat::Json));
+        assert!(matches!(FileFormat::for_path("today"), FileFormat::Json));
+    }
+
+    #[test]
+    fn json_round_trips_through_serialize_and_deserialize() {
+        let value = sample();
+        let bytes = FileFormat::Json.serialize(&value).unwrap();
+        let restored: Sample = FileFormat::Json.deserialize(&bytes).unwrap();
+        assert_eq!(restored, value);
+    }
+
+    #[test]
+    fn messagepack_round_trips_through_serialize_and_deserialize() {
+        let value = sample();
+        let bytes = FileFormat::MessagePack.serialize(&value).unwrap();
+        let restored: Sample = FileFormat::MessagePack.deserialize(&bytes).unwrap();
+        assert_eq!(restored, value);
+    }
+
+    #[test]
+    fn messagepack_is_more_compact_than_json_for_the_same_value() {
+        let value = sample();
+        let json_bytes = FileFormat::Json.serialize(&value).unwrap();
+        let mp_bytes = FileFormat::MessagePack.serialize(&value).unwrap();
+        assert!(mp_bytes.len() < json_bytes.len());
+    }
+
+    #[test]
+    fn save_then_load_round_trips_through_the_chosen_backend() {
+        let mut path = std::env::temp_dir();
+        path.push(format!("todo_format_test_{}.mp", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        let value = sample();
+        save(path, &value).unwrap();
+        let restored: Sample = load(path).unwrap();
+        assert_eq!(restored, value);
+
+        let _ = std::fs::remove_file(path);
+    }
+}
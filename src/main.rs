@@ -13,7 +13,12 @@ use tui::widgets::{List, ListItem, Paragraph};
 use tui::{Terminal, backend::CrosstermBackend};
 
 use chrono::prelude::*;
+mod dates;
 mod events;
+mod export;
+mod format;
+mod sync;
+use dates::parse_fuzzy_date;
 use events::*;
 
 // the tasks for today
@@ -73,6 +78,14 @@ impl Default for WeekdayTasks {
 enum AppMode {
     Edit,
     Insert,
+    // prompts for an optional deadline while a new task is being created
+    InsertDeadline,
+    // prompts for optional comma-separated tags while a new task is being created
+    InsertTags,
+    // prompts for an optional "when" date on the currently selected task
+    InsertWhen,
+    // prompts for a tag to restrict the visible list to
+    Filter,
 }
 
 #[derive(Serialize, Deserialize, Debug, Copy, Clone)]
@@ -81,10 +94,60 @@ enum Status {
     Done,
 }
 
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Priority {
+    Low,
+    Medium,
+    High,
+}
+
+impl Default for Priority {
+    fn default() -> Self {
+        Priority::Low
+    }
+}
+
+impl Priority {
+    // cycle to the next priority, wrapping back to Low after High
+    fn cycle(self) -> Self {
+        match self {
+            Priority::Low => Priority::Medium,
+            Priority::Medium => Priority::High,
+            Priority::High => Priority::Low,
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            Priority::Low => Color::Green,
+            Priority::Medium => Color::Yellow,
+            Priority::High => Color::Red,
+        }
+    }
+}
+
+// one start/stop cycle of the timer; `duration_seconds` is None while the
+// entry is still running
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct TimeEntry {
+    start: chrono::NaiveDateTime,
+    duration_seconds: Option<i64>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 struct Task {
     status: Status,
     info: String,
+    #[serde(default)]
+    priority: Priority,
+    #[serde(default)]
+    when: Option<chrono::NaiveDateTime>,
+    #[serde(default)]
+    deadline: Option<chrono::NaiveDateTime>,
+    #[serde(default)]
+    time_entries: Vec<TimeEntry>,
+    #[serde(default)]
+    tags: Vec<String>,
 }
 
 #[cfg(target_os = "windows")]
@@ -104,84 +167,205 @@ fn get_status_char(status: &Status) -> &str {
 }
 
 impl Task {
+    fn is_overdue(&self) -> bool {
+        match self.deadline {
+            Some(deadline) => deadline < Local::now().naive_local(),
+            None => false,
+        }
+    }
+
+    fn is_timer_running(&self) -> bool {
+        matches!(self.time_entries.last(), Some(entry) if entry.duration_seconds.is_none())
+    }
+
+    // starts a new entry, or closes the open one, recording elapsed time
+    fn toggle_timer(&mut self) {
+        if self.is_timer_running() {
+            let entry = self.time_entries.last_mut().unwrap();
+            let elapsed = Local::now().naive_local() - entry.start;
+            entry.duration_seconds = Some(elapsed.num_seconds());
+        } else {
+            self.time_entries.push(TimeEntry {
+                start: Local::now().naive_local(),
+                duration_seconds: None,
+            });
+        }
+    }
+
+    fn total_tracked_seconds(&self) -> i64 {
+        self.time_entries
+            .iter()
+            .map(|entry| match entry.duration_seconds {
+                Some(seconds) => seconds,
+                None => (Local::now().naive_local() - entry.start).num_seconds(),
+            })
+            .sum()
+    }
+
     fn into_list_item(&self) -> ListItem {
         let box_token: &str = get_status_char(&self.status);
 
-        let span = Span::raw(format!("{} {}", box_token, self.info));
+        let mut text = format!("{} {}", box_token, self.info);
+        if let Some(when) = self.when {
+            text.push_str(&format!(" (when {})", when.format("%m-%d %H:%M")));
+        }
+        if let Some(deadline) = self.deadline {
+            text.push_str(&format!(" (due {})", deadline.format("%m-%d %H:%M")));
+        }
+        let tracked = self.total_tracked_seconds();
+        if tracked > 0 || self.is_timer_running() {
+            let marker = if self.is_timer_running() { "*" } else { "" };
+            text.push_str(&format!(" [{}{}]", format_duration(tracked), marker));
+        }
+        if !self.tags.is_empty() {
+            text.push_str(&format!(" #{}", self.tags.join(" #")));
+        }
+
+        let span = Span::styled(text, Style::default().fg(self.priority.color()));
         ListItem::new(span)
     }
 }
 
-fn save_today(tasks: &Vec<Task>, path: &str) {
-    let local: DateTime<Local> = Local::now();
-    let today = Today {
-        tasks: tasks.to_vec(),
-        date: Some(local),
-    };
-    let serialized = serde_json::to_string(&today).unwrap();
-    let _save_result = std::fs::write(path, serialized);
-    // println!("tried saving file {}, result: {:?}", path, _save_result);
+fn format_duration(total_seconds: i64) -> String {
+    format!("{}:{:02}", total_seconds / 3600, (total_seconds % 3600) / 60)
 }
 
-fn main() -> Result<(), io::Error> {
-    let mut working_path = std::env::current_exe().unwrap();
-    // get rid of application name
-    working_path.pop();
-    let daily_path = format!(
-        "{}/{}",
-        working_path.to_str().unwrap(),
-        "daily_occuring.json"
-    );
-    let today_path = format!("{}/{}", working_path.to_str().unwrap(), "today.json");
-    // println!("files path: {:?}", today_path);
+// stable sort so higher-priority tasks surface first, ties keeping their relative order
+fn sort_tasks_by_priority(tasks: &mut Vec<Task>) {
+    tasks.sort_by_key(|task| std::cmp::Reverse(task.priority));
+}
 
-    let file_result = std::fs::read_to_string(&daily_path);
-    let weekday_tasks = match file_result {
-        Ok(file_string) => {
-            let daily_occuring = serde_json::from_str(&file_string).expect("corrupt file");
-            daily_occuring
-        }
-        Err(_file_error) => {
-            // return default daily occuring data
+// indices of `tasks` shown under the active tag filter, in task order; shared
+// between the render pass and anywhere that needs to re-locate a task by its
+// view position after the underlying list changes shape
+fn visible_task_indices(tasks: &[Task], filter_tag: &Option<String>) -> Vec<usize> {
+    match filter_tag {
+        Some(tag) => tasks
+            .iter()
+            .enumerate()
+            .filter(|(_, task)| task.tags.iter().any(|t| t == tag))
+            .map(|(index, _)| index)
+            .collect(),
+        None => (0..tasks.len()).collect(),
+    }
+}
+
+const MAX_UNDO_DEPTH: usize = 50;
+
+// snapshot the current tasks before a mutating keystroke, so 'u' can restore it.
+// a fresh action invalidates the redo history.
+fn record_undo(undo_stack: &mut Vec<Vec<Task>>, redo_stack: &mut Vec<Vec<Task>>, tasks: &[Task]) {
+    undo_stack.push(tasks.to_vec());
+    if undo_stack.len() > MAX_UNDO_DEPTH {
+        undo_stack.remove(0);
+    }
+    redo_stack.clear();
+}
+
+// reads the recurring weekday tasks, seeding the file with defaults on first run
+fn load_weekday_tasks(daily_path: &str) -> WeekdayTasks {
+    match format::load::<WeekdayTasks>(daily_path) {
+        Ok(weekday_tasks) => weekday_tasks,
+        Err(_error) => {
             let default_weekday_tasks = WeekdayTasks::default();
-            let serialized = serde_json::to_string(&default_weekday_tasks).unwrap();
-            let _save_result = std::fs::write(&daily_path, serialized);
-            // println!("saved to daily_occuring? {:?}", _save_result);
+            let _save_result = format::save(daily_path, &default_weekday_tasks);
             default_weekday_tasks
         }
-    };
-    let local: DateTime<Local> = Local::now();
-    let weekday = local.date().weekday();
+    }
+}
+
+// builds today's task list from the recurring weekday tasks, then overlays
+// whatever was saved for today (if it's still the same weekday). Returns an
+// error if `today_path` exists but can't be parsed (e.g. left full of git
+// conflict markers by a failed sync) so the caller can tell that apart from
+// there simply being nothing saved yet, instead of silently losing edits.
+fn load_tasks(weekday_tasks: &WeekdayTasks, weekday: Weekday, today_path: &str) -> Result<Vec<Task>, io::Error> {
     let mut tasks = Vec::<Task>::new();
     if let Some(day_tasks) = weekday_tasks.tasks.get(&weekday) {
         for day_task in day_tasks.tasks.iter() {
             tasks.push(Task {
                 status: Status::Todo,
                 info: day_task.clone(),
+                priority: Priority::default(),
+                when: None,
+                deadline: None,
+                time_entries: Vec::new(),
+                tags: Vec::new(),
             });
         }
     }
     // check if we have a save for today
-    let today_file_result = std::fs::read_to_string(&today_path);
-    if let Ok(today_string) = today_file_result {
-        if let Ok(today) = serde_json::from_str::<Today>(&today_string) {
+    match format::load::<Today>(today_path) {
+        Ok(today) => {
             if let Some(today_date) = today.date {
                 if today_date.weekday() == weekday {
                     for saved_task in today.tasks.into_iter() {
-                        for loaded_task in tasks.iter_mut() {
-                            if loaded_task.info == saved_task.info {
-                                loaded_task.status = saved_task.status;
-                            }
-                        }
-                        if !tasks.iter().any(|t| t.info == saved_task.info) {
-                            tasks.push(saved_task);
+                        // the saved copy carries every field (priority, deadline, tags,
+                        // time entries, ...), so it replaces the recurring one wholesale
+                        // rather than patching a single field onto it
+                        match tasks.iter_mut().find(|t| t.info == saved_task.info) {
+                            Some(existing) => *existing = saved_task,
+                            None => tasks.push(saved_task),
                         }
                     }
                 }
             }
         }
+        // no file yet just means nothing has been saved for today, not a failure
+        Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+        Err(error) => return Err(error),
     }
 
+    sort_tasks_by_priority(&mut tasks);
+    Ok(tasks)
+}
+
+fn save_today(tasks: &Vec<Task>, path: &str) {
+    let local: DateTime<Local> = Local::now();
+    let today = Today {
+        tasks: tasks.to_vec(),
+        date: Some(local),
+    };
+    let _save_result = format::save(path, &today);
+    // println!("tried saving file {}, result: {:?}", path, _save_result);
+}
+
+#[tokio::main]
+async fn main() -> Result<(), io::Error> {
+    let mut working_path = std::env::current_exe().unwrap();
+    // get rid of application name
+    working_path.pop();
+    let storage_config_path = format!("{}/{}", working_path.to_str().unwrap(), "storage_config.json");
+    let storage_config = format::load_or_init_storage_config(&storage_config_path);
+    let daily_path = format::with_storage_extension(
+        &format!("{}/{}", working_path.to_str().unwrap(), "daily_occuring.json"),
+        &storage_config,
+    );
+    let today_path = format::with_storage_extension(
+        &format!("{}/{}", working_path.to_str().unwrap(), "today.json"),
+        &storage_config,
+    );
+    // println!("files path: {:?}", today_path);
+    let sync_config_path = format!("{}/{}", working_path.to_str().unwrap(), "sync_config.json");
+    let sync_config = sync::load_or_init(&sync_config_path, working_path.to_str().unwrap());
+
+    // `todo export [week_anchor]` writes a weekly calendar and exits, skipping the TUI
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("export") {
+        let weekday_tasks = load_weekday_tasks(&daily_path);
+        let week_anchor = args.get(2).map(String::as_str);
+        export::export_week(&weekday_tasks, &today_path, week_anchor, working_path.to_str().unwrap())?;
+        return Ok(());
+    }
+
+    let weekday_tasks = load_weekday_tasks(&daily_path);
+    let local: DateTime<Local> = Local::now();
+    let weekday = local.date().weekday();
+    let mut tasks = load_tasks(&weekday_tasks, weekday, &today_path).unwrap_or_else(|error| {
+        eprintln!("failed to load {}: {}", today_path, error);
+        Vec::new()
+    });
+
     //let stdout = io::stdout().into_raw_mode()?;
     enable_raw_mode().unwrap();
     let stdout = io::stdout();
@@ -190,11 +374,25 @@ fn main() -> Result<(), io::Error> {
     terminal.clear().unwrap();
 
     let mut selected: i32 = 0;
-    let events = Events::new();
+    let mut events = Events::new(&[&today_path, &daily_path]);
     let mut app_mode = AppMode::Edit;
     let mut input_string = String::new();
+    // holds the task text while we prompt for its (optional) deadline, then its tags
+    let mut pending_task_info = String::new();
+    let mut pending_deadline: Option<chrono::NaiveDateTime> = None;
+    let mut show_timesheet = false;
+    let mut filter_tag: Option<String> = None;
+    let mut status_message: Option<String> = None;
+    let mut undo_stack: Vec<Vec<Task>> = Vec::new();
+    let mut redo_stack: Vec<Vec<Task>> = Vec::new();
+    // delivers the result of a background `S` sync without blocking the event loop
+    let (sync_tx, mut sync_rx) = tokio::sync::mpsc::unbounded_channel::<Result<String, String>>();
+    let mut syncing = false;
 
     loop {
+        // which tasks are shown (and addressable by `selected`) given the active tag filter
+        let visible_indices = visible_task_indices(&tasks, &filter_tag);
+
         terminal.draw(|f| {
             let chunks = Layout::default()
                 .direction(Direction::Vertical)
@@ -209,13 +407,18 @@ fn main() -> Result<(), io::Error> {
                 )
                 .split(f.size());
 
-            let items: Vec<ListItem> = tasks
+            let items: Vec<ListItem> = visible_indices
                 .iter()
                 .enumerate()
-                .map(|(index, task)| {
+                .map(|(view_index, &task_index)| {
+                    let task = &tasks[task_index];
                     let mut list_item = task.into_list_item();
+                    // overdue tasks stand out even when not selected
+                    if task.is_overdue() {
+                        list_item = ListItem::style(list_item, Style::default().bg(Color::Red));
+                    }
                     // modify style if selected
-                    if index == selected as usize {
+                    if view_index == selected as usize {
                         list_item = ListItem::style(list_item, Style::default().bg(Color::Magenta));
                     }
 
@@ -223,13 +426,19 @@ fn main() -> Result<(), io::Error> {
                 })
                 .collect();
 
-            let title = match weekday_tasks.tasks.get(&weekday) {
-                Some(task_data) => Paragraph::new(Text::raw(format!(
+            let title = match (weekday_tasks.tasks.get(&weekday), &filter_tag) {
+                (Some(task_data), None) => Paragraph::new(Text::raw(format!(
                     "{}: {}",
                     weekday,
                     task_data.day_info.clone()
                 ))),
-                None => Paragraph::new(Text::raw(format!(
+                (Some(task_data), Some(tag)) => Paragraph::new(Text::raw(format!(
+                    "{}: {} (filtered by #{})",
+                    weekday,
+                    task_data.day_info.clone(),
+                    tag
+                ))),
+                (None, _) => Paragraph::new(Text::raw(format!(
                     "no daily occuring task for {:?}",
                     weekday
                 ))),
@@ -239,9 +448,17 @@ fn main() -> Result<(), io::Error> {
             let list = List::new(items);
             f.render_widget(list, chunks[1]);
 
-            if AppMode::Insert == app_mode {
+            let input_title = match app_mode {
+                AppMode::Insert => Some("new task"),
+                AppMode::InsertDeadline => Some("due (today, tomorrow, fri, +3d, 14:30... enter to skip)"),
+                AppMode::InsertTags => Some("tags, comma separated (enter to skip)"),
+                AppMode::InsertWhen => Some("when (today, tomorrow, fri, +3d, 14:30... enter to skip)"),
+                AppMode::Filter => Some("filter by tag (enter to skip, F clears)"),
+                AppMode::Edit => None,
+            };
+            if let Some(input_title) = input_title {
                 let input = Paragraph::new(Text::raw(input_string.as_str()))
-                    .block(Block::default().borders(Borders::ALL).title("new task"));
+                    .block(Block::default().borders(Borders::ALL).title(input_title));
                 f.render_widget(input, chunks[2]);
                 f.set_cursor(
                     // Put cursor past the end of the input text
@@ -249,57 +466,182 @@ fn main() -> Result<(), io::Error> {
                     // Move one line down, from the border to the input line
                     chunks[2].y + 1,
                 )
+            } else if let Some(message) = &status_message {
+                let status = Paragraph::new(Text::raw(message.as_str()))
+                    .block(Block::default().borders(Borders::ALL).title("status"));
+                f.render_widget(status, chunks[2]);
+            } else if show_timesheet {
+                let total_seconds: i64 = tasks.iter().map(|t| t.total_tracked_seconds()).sum();
+                let timesheet = Paragraph::new(Text::raw(format!(
+                    "total tracked today: {}",
+                    format_duration(total_seconds)
+                )))
+                .block(Block::default().borders(Borders::ALL).title("timesheet"));
+                f.render_widget(timesheet, chunks[2]);
             }
         })?;
 
-        for event in events.next() {
-            if let Event::Input(input) = event {
+        let event = tokio::select! {
+            event = events.next() => event,
+            Some(sync_result) = sync_rx.recv() => {
+                syncing = false;
+                status_message = Some(match sync_result {
+                    Ok(_) => "synced".to_string(),
+                    Err(error) => format!("sync failed: {}", error.trim()),
+                });
+                continue;
+            }
+        };
+
+        if let Some(event) = event {
+            match event {
+                Event::Tick => {}
+                Event::FilesChanged => {
+                    match load_tasks(&weekday_tasks, weekday, &today_path) {
+                        Ok(reloaded) => {
+                            tasks = reloaded;
+                            let new_visible = visible_task_indices(&tasks, &filter_tag);
+                            if selected as usize >= new_visible.len() {
+                                selected = (new_visible.len() as i32 - 1).max(0);
+                            }
+                            status_message = Some("reloaded from disk".to_string());
+                        }
+                        Err(error) => {
+                            // keep the in-memory tasks as-is rather than stomping them
+                            // with an empty/partial reload of an unparseable file
+                            status_message = Some(format!("reload failed, kept in-memory tasks: {}", error));
+                        }
+                    }
+                }
+                Event::Input(input) => {
                 match app_mode {
                     AppMode::Edit => {
+                        // the task the cursor is actually over, through the active filter
+                        let actual_index = visible_indices.get(selected as usize).copied();
                         match input {
                             KeyCode::Char('j') => {
                                 selected += 1;
-                                if selected as usize >= tasks.len() {
+                                if selected as usize >= visible_indices.len() {
                                     selected = 0;
                                 }
                             }
                             KeyCode::Char('k') => {
                                 selected -= 1;
                                 if selected < 0 {
-                                    selected = (tasks.len() - 1) as i32;
+                                    selected = (visible_indices.len() as i32 - 1).max(0);
                                 }
                             }
                             KeyCode::Char('l') => {
                                 // modify the current selected task
-                                let mut task = tasks.get_mut(selected as usize).unwrap();
-                                task.status = Status::Done;
+                                if let Some(index) = actual_index {
+                                    record_undo(&mut undo_stack, &mut redo_stack, &tasks);
+                                    tasks[index].status = Status::Done;
+                                }
                             }
                             KeyCode::Char('h') => {
                                 // modify the current selected task
-                                let mut task = tasks.get_mut(selected as usize).unwrap();
-                                task.status = Status::Todo;
+                                if let Some(index) = actual_index {
+                                    record_undo(&mut undo_stack, &mut redo_stack, &tasks);
+                                    tasks[index].status = Status::Todo;
+                                }
+                            }
+                            KeyCode::Char('p') => {
+                                // cycle the priority of the selected task, then keep the
+                                // cursor on it even though resorting moves it to a new row
+                                if let Some(index) = actual_index {
+                                    record_undo(&mut undo_stack, &mut redo_stack, &tasks);
+                                    let mut task = tasks.remove(index);
+                                    task.priority = task.priority.cycle();
+                                    let new_index = tasks.partition_point(|t| t.priority >= task.priority);
+                                    tasks.insert(new_index, task);
+                                    let new_visible = visible_task_indices(&tasks, &filter_tag);
+                                    if let Some(view_index) = new_visible.iter().position(|&i| i == new_index) {
+                                        selected = view_index as i32;
+                                    }
+                                }
                             }
                             // enter insert mode
                             KeyCode::Char('i') => {
                                 // modify the current selected task
                                 app_mode = AppMode::Insert;
                             }
+                            KeyCode::Char('w') => {
+                                // set the "when" date on the selected task
+                                if actual_index.is_some() {
+                                    app_mode = AppMode::InsertWhen;
+                                }
+                            }
+                            KeyCode::Char('s') => {
+                                // start/stop the timer on the selected task
+                                if let Some(index) = actual_index {
+                                    tasks[index].toggle_timer();
+                                }
+                            }
+                            KeyCode::Char('f') => {
+                                // prompt for a tag to filter the list by
+                                app_mode = AppMode::Filter;
+                            }
+                            KeyCode::Char('F') => {
+                                // clear the active filter
+                                filter_tag = None;
+                                selected = 0;
+                            }
+                            KeyCode::Char('t') => {
+                                // toggle the timesheet summary pane
+                                show_timesheet = !show_timesheet;
+                            }
+                            KeyCode::Char('S') => {
+                                // commit and sync the state files against the configured remote;
+                                // the git calls are blocking, so run them on a blocking thread
+                                // instead of freezing the TUI for the duration of the network call
+                                if !syncing {
+                                    save_today(&tasks, &today_path);
+                                    syncing = true;
+                                    status_message = Some("syncing...".to_string());
+                                    let sync_config = sync_config.clone();
+                                    let sync_tx = sync_tx.clone();
+                                    let today_path = today_path.clone();
+                                    let daily_path = daily_path.clone();
+                                    tokio::task::spawn_blocking(move || {
+                                        let _ = sync_tx.send(sync::sync(&sync_config, &today_path, &daily_path));
+                                    });
+                                }
+                            }
+                            KeyCode::Char('u') => {
+                                // undo the last mutation
+                                if let Some(previous) = undo_stack.pop() {
+                                    redo_stack.push(tasks.clone());
+                                    tasks = previous;
+                                    let new_visible = visible_task_indices(&tasks, &filter_tag);
+                                    if selected as usize >= new_visible.len() {
+                                        selected = (new_visible.len() as i32 - 1).max(0);
+                                    }
+                                }
+                            }
+                            KeyCode::Char('U') => {
+                                // redo the last undone mutation
+                                if let Some(next) = redo_stack.pop() {
+                                    undo_stack.push(tasks.clone());
+                                    tasks = next;
+                                    let new_visible = visible_task_indices(&tasks, &filter_tag);
+                                    if selected as usize >= new_visible.len() {
+                                        selected = (new_visible.len() as i32 - 1).max(0);
+                                    }
+                                }
+                            }
                             KeyCode::Char('x') => {
                                 // remove entry
-                                if selected >= 0 && selected < tasks.len() as i32 {
-                                    tasks.remove(selected as usize);
-                                    if selected as usize >= tasks.len() {
-                                        selected = tasks.len() as i32 - 1;
+                                if let Some(index) = actual_index {
+                                    record_undo(&mut undo_stack, &mut redo_stack, &tasks);
+                                    tasks.remove(index);
+                                    if selected as usize >= visible_indices.len().saturating_sub(1) {
+                                        selected = (visible_indices.len() as i32 - 2).max(0);
                                     }
                                 }
                             }
                             KeyCode::Char('q') => {
                                 save_today(&tasks, &today_path);
-<<<<<<< HEAD
                                 let _ = crossterm::terminal::disable_raw_mode();
-=======
-                                crossterm::terminal::disable_raw_mode().unwrap();
->>>>>>> 5bf935a4b03e47e605e3fed175b0671fa5f76ad6
                                 return Ok(());
                             }
                             _ => {}
@@ -313,12 +655,68 @@ fn main() -> Result<(), io::Error> {
                             }
                             KeyCode::Enter => {
                             //KeyCode::Char('\n') => {
-                                // submit
+                                // text is in, now prompt for an optional deadline
+                                pending_task_info = input_string.drain(..).collect();
+                                app_mode = AppMode::InsertDeadline;
+                            }
+                            KeyCode::Backspace => {
+                                input_string.pop();
+                            }
+                            KeyCode::Char(c) => {
+                                input_string.push(c);
+                            }
+                            _ => {}
+                        }
+                    }
+                    AppMode::InsertDeadline => {
+                        match input {
+                            KeyCode::Esc => {
+                                app_mode = AppMode::Edit;
+                                input_string.clear();
+                                pending_task_info.clear();
+                            }
+                            KeyCode::Enter => {
+                                pending_deadline = parse_fuzzy_date(&input_string, Local::now())
+                                    .map(|date| date.naive_local());
+                                input_string.clear();
+                                app_mode = AppMode::InsertTags;
+                            }
+                            KeyCode::Backspace => {
+                                input_string.pop();
+                            }
+                            KeyCode::Char(c) => {
+                                input_string.push(c);
+                            }
+                            _ => {}
+                        }
+                    }
+                    AppMode::InsertTags => {
+                        match input {
+                            KeyCode::Esc => {
+                                app_mode = AppMode::Edit;
+                                input_string.clear();
+                                pending_task_info.clear();
+                                pending_deadline = None;
+                            }
+                            KeyCode::Enter => {
+                                let tags: Vec<String> = input_string
+                                    .split(',')
+                                    .map(|tag| tag.trim().to_string())
+                                    .filter(|tag| !tag.is_empty())
+                                    .collect();
+                                input_string.clear();
                                 app_mode = AppMode::Edit;
+                                record_undo(&mut undo_stack, &mut redo_stack, &tasks);
                                 tasks.push(Task {
                                     status: Status::Todo,
-                                    info: input_string.drain(..).collect(),
+                                    info: pending_task_info.drain(..).collect(),
+                                    priority: Priority::default(),
+                                    when: None,
+                                    deadline: pending_deadline.take(),
+                                    time_entries: Vec::new(),
+                                    tags,
                                 });
+                                sort_tasks_by_priority(&mut tasks);
                             }
                             KeyCode::Backspace => {
                                 input_string.pop();
@@ -329,6 +727,57 @@ fn main() -> Result<(), io::Error> {
                             _ => {}
                         }
                     }
+                    AppMode::InsertWhen => {
+                        match input {
+                            KeyCode::Esc => {
+                                app_mode = AppMode::Edit;
+                                input_string.clear();
+                            }
+                            KeyCode::Enter => {
+                                let when = parse_fuzzy_date(&input_string, Local::now())
+                                    .map(|date| date.naive_local());
+                                input_string.clear();
+                                app_mode = AppMode::Edit;
+                                if let Some(&index) = visible_indices.get(selected as usize) {
+                                    tasks[index].when = when;
+                                }
+                            }
+                            KeyCode::Backspace => {
+                                input_string.pop();
+                            }
+                            KeyCode::Char(c) => {
+                                input_string.push(c);
+                            }
+                            _ => {}
+                        }
+                    }
+                    AppMode::Filter => {
+                        match input {
+                            KeyCode::Esc => {
+                                app_mode = AppMode::Edit;
+                                input_string.clear();
+                            }
+                            KeyCode::Enter => {
+                                let tag = input_string.trim();
+                                filter_tag = if tag.is_empty() {
+                                    None
+                                } else {
+                                    Some(tag.to_string())
+                                };
+                                input_string.clear();
+                                app_mode = AppMode::Edit;
+                                selected = 0;
+                            }
+                            KeyCode::Backspace => {
+                                input_string.pop();
+                            }
+                            KeyCode::Char(c) => {
+                                input_string.push(c);
+                            }
+                            _ => {}
+                        }
+                    }
+                }
                 }
             }
         }
@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+
+// where to sync the state files, and what git remote to push/pull against
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SyncConfig {
+    pub repo_path: String,
+    pub remote: String,
+}
+
+impl SyncConfig {
+    fn default_at(repo_path: &str) -> Self {
+        Self {
+            repo_path: repo_path.to_string(),
+            remote: "origin".to_string(),
+        }
+    }
+}
+
+pub fn load_or_init(config_path: &str, repo_path: &str) -> SyncConfig {
+    match std::fs::read_to_string(config_path) {
+        Ok(file_string) => {
+            serde_json::from_str(&file_string).unwrap_or_else(|_| SyncConfig::default_at(repo_path))
+        }
+        Err(_file_error) => {
+            let default_config = SyncConfig::default_at(repo_path);
+            let serialized = serde_json::to_string(&default_config).unwrap();
+            let _save_result = std::fs::write(config_path, serialized);
+            default_config
+        }
+    }
+}
+
+// commit the current state files and pull-then-push against the configured
+// remote, returning a short status line for the user instead of panicking
+pub fn sync(config: &SyncConfig, today_path: &str, daily_path: &str) -> Result<String, String> {
+    let message = format!("todo sync {}", chrono::Local::now().format("%Y-%m-%d %H:%M:%S"));
+
+    run_git(&config.repo_path, &["add", today_path, daily_path])?;
+    // an empty commit (nothing changed) is not an error
+    let _ = run_git(&config.repo_path, &["commit", "-m", &message]);
+
+    if let Err(pull_error) = run_git(&config.repo_path, &["pull", "--rebase", &config.remote]) {
+        // leave the repo mid-rebase and every future sync keeps failing with the
+        // same conflict; abort back to a clean working tree so the user can retry
+        let _ = run_git(&config.repo_path, &["rebase", "--abort"]);
+        return Err(pull_error);
+    }
+    run_git(&config.repo_path, &["push", &config.remote])?;
+
+    Ok("synced".to_string())
+}
+
+fn run_git(repo_path: &str, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo_path)
+        .args(args)
+        .output()
+        .map_err(|err| format!("failed to run git: {}", err))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).to_string())
+    }
+}